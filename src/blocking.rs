@@ -0,0 +1,540 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The synchronous, thread-blocking implementation of this crate, built on
+//! [dbus]'s blocking [Connection].
+//!
+//! This is always available. See [crate::non_blocking] for a Tokio-based
+//! alternative, available behind the `async-tokio` feature.
+
+use std::collections::HashMap;
+
+use dbus::arg::RefArg;
+use dbus::{
+    arg::{PropMap, Variant},
+    blocking::{Connection, Proxy},
+    strings::Path,
+};
+
+use crate::proxy::{new_proxy, service::Service};
+use crate::session::Session;
+use crate::ss::{SS_COLLECTION_LABEL, SS_DBUS_PATH};
+use crate::{Collection, EncryptionType, Error, Item, LockAction, SearchItemsResult};
+
+/// Secret Service Struct.
+///
+/// This the main entry point for usage of the library.
+///
+/// Creating a new [SecretService] will also initialize dbus
+/// and negotiate a new cryptographic session
+/// ([EncryptionType::Plain] or [EncryptionType::Dh])
+pub struct SecretService {
+    pub(crate) connection: Connection,
+    pub(crate) session: Session,
+    pub(crate) timeout: Option<u64>,
+}
+
+impl SecretService {
+    /// Connect to the DBus and return a new [SecretService] instance.
+    pub fn connect(encryption: EncryptionType) -> Result<Self, Error> {
+        let connection = Connection::new_session()?;
+        let session = Session::new(new_proxy(&connection, SS_DBUS_PATH), encryption)?;
+        Ok(SecretService {
+            connection,
+            session,
+            timeout: None,
+        })
+    }
+
+    /// Connect to the DBus and return a new [SecretService] instance.
+    ///
+    /// Instead of waiting indefinitely for users to respond to prompts,
+    /// this instance will time them out after a given number of seconds.
+    /// (Specifying 0 for the number of seconds will prevent the prompt
+    /// from appearing at all.)
+    pub fn connect_with_max_prompt_timeout(
+        encryption: EncryptionType,
+        seconds: u64,
+    ) -> Result<Self, Error> {
+        let mut service = Self::connect(encryption)?;
+        service.timeout = Some(seconds);
+        Ok(service)
+    }
+
+    /// Get the service proxy (internal)
+    pub(crate) fn proxy<'a>(&'a self) -> Proxy<'a, &'a Connection> {
+        new_proxy(&self.connection, SS_DBUS_PATH)
+    }
+
+    /// The [EncryptionType] that was actually negotiated with the service
+    /// when this [SecretService] was created, as opposed to the one
+    /// requested at [connect](Self::connect) time.
+    pub fn encryption_type(&self) -> EncryptionType {
+        self.session.encryption_type()
+    }
+
+    /// The algorithm string negotiated with the service at `connect` time,
+    /// e.g. `"dh-ietf1024-sha256-aes128-cbc-pkcs7"` or `"plain"`.
+    pub fn negotiated_algorithm(&self) -> &str {
+        self.session.algorithm()
+    }
+
+    /// Whether secrets exchanged with the service are actually encrypted in
+    /// transit.
+    ///
+    /// Callers that require confidentiality should check this after
+    /// [connect](Self::connect) and refuse to proceed if the service
+    /// silently downgraded to [EncryptionType::Plain].
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.encryption_type(), EncryptionType::Dh)
+    }
+
+    /// Get all collections
+    pub fn get_all_collections(&self) -> Result<Vec<Collection<'_>>, Error> {
+        let paths = self.proxy().collections()?;
+        let collections = paths
+            .into_iter()
+            .map(|path| Collection::new(self, path))
+            .collect();
+        Ok(collections)
+    }
+
+    /// Get collection by alias.
+    ///
+    /// Most common would be the `default` alias, but there
+    /// is also a specific method for getting the collection
+    /// by default alias.
+    pub fn get_collection_by_alias(&self, alias: &str) -> Result<Collection<'_>, Error> {
+        let path = self.proxy().read_alias(alias)?;
+        if path == Path::new("/")? {
+            Err(Error::NoResult)
+        } else {
+            Ok(Collection::new(self, path))
+        }
+    }
+
+    /// Get default collection.
+    /// (The collection whose alias is `default`)
+    pub fn get_default_collection(&self) -> Result<Collection<'_>, Error> {
+        self.get_collection_by_alias("default")
+    }
+
+    /// Get any collection.
+    /// First tries `default` collection, then `session`
+    /// collection, then the first collection when it
+    /// gets all collections.
+    pub fn get_any_collection(&self) -> Result<Collection<'_>, Error> {
+        self.get_default_collection()
+            .or_else(|_| self.get_collection_by_alias("session"))
+            .or_else(|_| {
+                let mut collections = self.get_all_collections()?;
+                if collections.is_empty() {
+                    Err(Error::NoResult)
+                } else {
+                    Ok(collections.swap_remove(0))
+                }
+            })
+    }
+
+    /// Creates a new collection with a label and an alias.
+    pub fn create_collection(&self, label: &str, alias: &str) -> Result<Collection<'_>, Error> {
+        let mut properties: PropMap = HashMap::new();
+        properties.insert(
+            SS_COLLECTION_LABEL.to_string(),
+            Variant(Box::new(label.to_string()) as Box<dyn RefArg>),
+        );
+        // create collection returning collection path and prompt path
+        let (c_path, p_path) = self.proxy().create_collection(properties, alias)?;
+        let created = {
+            if c_path == Path::new("/")? {
+                // no creation path, so prompt
+                self.prompt_for_create(&p_path)?
+            } else {
+                c_path
+            }
+        };
+        Ok(Collection::new(self, created))
+    }
+
+    /// Gets the collection with the given `alias`, creating it with `label`
+    /// if it doesn't exist yet, then ensures it is unlocked before returning
+    /// it.
+    ///
+    /// This saves callers the usual `read_alias` / `create_collection` /
+    /// `unlock` dance, including driving any unlock prompt through
+    /// [prompt_for_lock_unlock_delete](Self::prompt_for_lock_unlock_delete)
+    /// and honoring the configured [timeout](Self::connect_with_max_prompt_timeout).
+    pub fn get_or_create_collection_unlocked(
+        &self,
+        alias: &str,
+        label: &str,
+    ) -> Result<Collection<'_>, Error> {
+        let path = self.proxy().read_alias(alias)?;
+        let collection = if path == Path::new("/")? {
+            self.create_collection(label, alias)?
+        } else {
+            Collection::new(self, path)
+        };
+        self.lock_unlock_all(LockAction::Unlock, vec![collection.path.clone()])?;
+        Ok(collection)
+    }
+
+    /// Convenience wrapper around
+    /// [get_or_create_collection_unlocked](Self::get_or_create_collection_unlocked)
+    /// for the `default` collection.
+    pub fn get_default_collection_unlocked(&self) -> Result<Collection<'_>, Error> {
+        self.get_or_create_collection_unlocked("default", "Default collection")
+    }
+
+    /// Searches all items by attributes
+    pub fn search_items(
+        &self,
+        attributes: HashMap<&str, &str>,
+    ) -> Result<SearchItemsResult<Item<'_>>, Error> {
+        let (unlocked, locked) = self.proxy().search_items(attributes)?;
+        let result = SearchItemsResult {
+            unlocked: unlocked.into_iter().map(|p| Item::new(self, p)).collect(),
+            locked: locked.into_iter().map(|p| Item::new(self, p)).collect(),
+        };
+        Ok(result)
+    }
+
+    /// Lock all items in a batch
+    pub fn lock_all(&self, items: &[&Item<'_>]) -> Result<(), Error> {
+        let paths = items.iter().map(|i| i.path.clone()).collect();
+        self.lock_unlock_all(LockAction::Lock, paths)
+    }
+
+    /// Unlock all items in a batch
+    pub fn unlock_all(&self, items: &[&Item<'_>]) -> Result<(), Error> {
+        let paths = items.iter().map(|i| i.path.clone()).collect();
+        self.lock_unlock_all(LockAction::Unlock, paths)
+    }
+
+    /// Retrieves the secrets of several `items` in a single `GetSecrets`
+    /// D-Bus round trip instead of one `GetSecret` call per item.
+    ///
+    /// Each returned value is decrypted with the current session's AES key
+    /// using that item's own IV, the same way [Item::get_secret] decrypts a
+    /// single secret. Locked items are simply omitted by the service, so the
+    /// returned map may contain fewer entries than `items`; it is keyed by
+    /// object path so the caller can tell which ones came back.
+    pub fn get_secrets(
+        &self,
+        items: &[&Item<'_>],
+    ) -> Result<HashMap<Path<'static>, (Vec<u8>, String)>, Error> {
+        let paths = items.iter().map(|i| i.path.clone()).collect();
+        let secrets = self.proxy().get_secrets(paths, self.session.object_path())?;
+        secrets
+            .into_iter()
+            .map(|(path, (_session, parameters, value, content_type))| {
+                let value = self.session.decrypt(&value, &parameters)?;
+                Ok((path, (value, content_type)))
+            })
+            .collect()
+    }
+
+    pub(crate) fn lock_unlock_all(
+        &self,
+        action: LockAction,
+        paths: Vec<Path<'static>>,
+    ) -> Result<(), Error> {
+        let (_, p_path) = match action {
+            LockAction::Lock => self.proxy().lock(paths)?,
+            LockAction::Unlock => self.proxy().unlock(paths)?,
+        };
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.prompt_for_lock_unlock_delete(&p_path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn should_create_secret_service() {
+        SecretService::connect(EncryptionType::Plain).unwrap();
+    }
+
+    #[test]
+    fn should_get_all_collections() {
+        // Assumes that there will always be a default collection
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collections = ss.get_all_collections().unwrap();
+        assert!(!collections.is_empty(), "no collections found");
+    }
+
+    #[test]
+    fn should_get_collection_by_alias() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        ss.get_collection_by_alias("session").unwrap();
+    }
+
+    #[test]
+    fn should_return_error_if_collection_doesnt_exist() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+
+        match ss.get_collection_by_alias("definitely_definitely_does_not_exist") {
+            Err(Error::NoResult) => {}
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn should_get_default_collection() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        ss.get_default_collection().unwrap();
+    }
+
+    #[test]
+    fn should_get_any_collection() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let _ = ss.get_any_collection().unwrap();
+    }
+
+    #[test_with::no_env(GITHUB_ACTIONS)] // can't run headless - prompts
+    fn should_create_and_delete_collection() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let test_collection = ss.create_collection("TestCreateDelete", "").unwrap();
+        assert!(test_collection
+            .path
+            .starts_with("/org/freedesktop/secrets/collection/Test"));
+        test_collection.delete().unwrap();
+    }
+
+    #[test]
+    fn should_search_items() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collection = ss.get_default_collection().unwrap();
+
+        // Create an item
+        let item = collection
+            .create_item(
+                "test",
+                HashMap::from([("test_attribute_in_ss", "test_value")]),
+                b"test_secret",
+                false,
+                "text/plain",
+            )
+            .unwrap();
+
+        // handle empty vec search
+        ss.search_items(HashMap::new()).unwrap();
+
+        // handle no result
+        let bad_search = ss.search_items(HashMap::from([("test", "test")])).unwrap();
+        assert_eq!(bad_search.unlocked.len(), 0);
+        assert_eq!(bad_search.locked.len(), 0);
+
+        // handle correct search for item and compare
+        let search_item = ss
+            .search_items(HashMap::from([("test_attribute_in_ss", "test_value")]))
+            .unwrap();
+
+        assert_eq!(item.path, search_item.unlocked[0].path);
+        assert_eq!(search_item.locked.len(), 0);
+        item.delete().unwrap();
+    }
+
+    #[test_with::no_env(GITHUB_ACTIONS)] // can't run headless - prompts
+    fn should_lock_and_unlock() {
+        // Assumes that there will always be at least one collection
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collections = ss.get_all_collections().unwrap();
+        assert!(!collections.is_empty(), "no collections found");
+        let paths: Vec<Path<'static>> = collections.iter().map(|c| c.path.clone()).collect();
+        ss.lock_unlock_all(LockAction::Lock, paths.clone()).unwrap();
+        ss.lock_unlock_all(LockAction::Unlock, paths).unwrap();
+    }
+
+    #[test]
+    fn should_report_negotiated_encryption() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        assert_eq!(ss.encryption_type(), EncryptionType::Plain);
+        assert_eq!(ss.negotiated_algorithm(), "plain");
+        assert!(!ss.is_encrypted());
+
+        let ss = SecretService::connect(EncryptionType::Dh).unwrap();
+        assert_eq!(ss.encryption_type(), EncryptionType::Dh);
+        assert!(ss.is_encrypted());
+    }
+
+    #[test_with::no_env(GITHUB_ACTIONS)] // can't run headless - prompts
+    fn should_get_or_create_collection_unlocked() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collection = ss
+            .get_or_create_collection_unlocked("test_get_or_create", "TestGetOrCreate")
+            .unwrap();
+        assert!(collection
+            .path
+            .starts_with("/org/freedesktop/secrets/collection/Test"));
+
+        // Calling it again should find the now-existing alias instead of
+        // creating a second collection.
+        let same_collection = ss
+            .get_or_create_collection_unlocked("test_get_or_create", "TestGetOrCreate")
+            .unwrap();
+        assert_eq!(collection.path, same_collection.path);
+
+        collection.delete().unwrap();
+    }
+
+    #[test]
+    fn should_get_default_collection_unlocked() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        ss.get_default_collection_unlocked().unwrap();
+    }
+
+    #[test]
+    fn should_get_secrets() {
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collection = ss.get_default_collection().unwrap();
+
+        let item_one = collection
+            .create_item(
+                "test_get_secrets_one",
+                HashMap::from([("test_get_secrets", "one")]),
+                b"secret_one",
+                false,
+                "text/plain",
+            )
+            .unwrap();
+        let item_two = collection
+            .create_item(
+                "test_get_secrets_two",
+                HashMap::from([("test_get_secrets", "two")]),
+                b"secret_two",
+                false,
+                "text/plain",
+            )
+            .unwrap();
+
+        let secrets = ss.get_secrets(&[&item_one, &item_two]).unwrap();
+        assert_eq!(secrets.len(), 2);
+        assert_eq!(
+            secrets[&item_one.path],
+            (item_one.get_secret().unwrap(), "text/plain".to_string())
+        );
+        assert_eq!(
+            secrets[&item_two.path],
+            (item_two.get_secret().unwrap(), "text/plain".to_string())
+        );
+
+        item_one.delete().unwrap();
+        item_two.delete().unwrap();
+    }
+
+    /// Pumps `process_events` in a loop until `found` returns `true` or
+    /// `timeout` elapses, so watch tests don't depend on a single signal
+    /// arriving within one 100ms poll.
+    fn wait_for(ss: &SecretService, timeout: std::time::Duration, mut found: impl FnMut() -> bool) {
+        let deadline = std::time::Instant::now() + timeout;
+        while !found() && std::time::Instant::now() < deadline {
+            ss.process_events(std::time::Duration::from_millis(100)).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_watch_collection_for_item_events() {
+        use std::sync::{Arc, Mutex};
+
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+        let collection = ss.get_default_collection().unwrap();
+
+        let events: Arc<Mutex<Vec<crate::CollectionEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let _guard = ss
+            .watch_collection(&collection, move |event| {
+                events_clone.lock().unwrap().push(event);
+            })
+            .unwrap();
+
+        let item = collection
+            .create_item(
+                "test_watch_collection",
+                HashMap::from([("test_watch_collection", "value")]),
+                b"test_secret",
+                false,
+                "text/plain",
+            )
+            .unwrap();
+
+        wait_for(&ss, std::time::Duration::from_secs(5), || {
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::CollectionEvent::ItemCreated(path) if *path == item.path)
+            })
+        });
+        assert!(
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::CollectionEvent::ItemCreated(path) if *path == item.path)
+            }),
+            "ItemCreated was never observed for {:?}",
+            item.path
+        );
+
+        item.delete().unwrap();
+
+        wait_for(&ss, std::time::Duration::from_secs(5), || {
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::CollectionEvent::ItemDeleted(path) if *path == item.path)
+            })
+        });
+        assert!(
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::CollectionEvent::ItemDeleted(path) if *path == item.path)
+            }),
+            "ItemDeleted was never observed for {:?}",
+            item.path
+        );
+    }
+
+    #[test_with::no_env(GITHUB_ACTIONS)] // can't run headless - prompts
+    fn should_watch_service_for_collection_events() {
+        use std::sync::{Arc, Mutex};
+
+        let ss = SecretService::connect(EncryptionType::Plain).unwrap();
+
+        let events: Arc<Mutex<Vec<crate::ServiceEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let _guard = ss.watch_service(move |event| events_clone.lock().unwrap().push(event)).unwrap();
+
+        let collection = ss.create_collection("TestWatchService", "").unwrap();
+
+        wait_for(&ss, std::time::Duration::from_secs(5), || {
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::ServiceEvent::CollectionCreated(path) if *path == collection.path)
+            })
+        });
+        assert!(
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::ServiceEvent::CollectionCreated(path) if *path == collection.path)
+            }),
+            "CollectionCreated was never observed for {:?}",
+            collection.path
+        );
+
+        collection.delete().unwrap();
+
+        wait_for(&ss, std::time::Duration::from_secs(5), || {
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::ServiceEvent::CollectionDeleted(path) if *path == collection.path)
+            })
+        });
+        assert!(
+            events.lock().unwrap().iter().any(|event| {
+                matches!(event, crate::ServiceEvent::CollectionDeleted(path) if *path == collection.path)
+            }),
+            "CollectionDeleted was never observed for {:?}",
+            collection.path
+        );
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A collection of secret items, as returned by
+//! [crate::blocking::SecretService::get_default_collection] and friends.
+
+use std::collections::HashMap;
+
+use dbus::strings::Path;
+
+use crate::proxy::collection::Collection as CollectionProxy;
+use crate::proxy::new_proxy;
+use crate::{blocking::SecretService, Error, Item, LockAction};
+
+/// A collection of secret items.
+pub struct Collection<'a> {
+    service: &'a SecretService,
+    pub path: Path<'static>,
+}
+
+impl<'a> Collection<'a> {
+    pub(crate) fn new(service: &'a SecretService, path: Path<'static>) -> Self {
+        Collection { service, path }
+    }
+
+    fn proxy(&self) -> dbus::blocking::Proxy<'_, &dbus::blocking::Connection> {
+        new_proxy(&self.service.connection, self.path.clone())
+    }
+
+    /// Creates a new item in this collection.
+    ///
+    /// If `replace` is true and an item with the same `attributes` already
+    /// exists, it's replaced with this one instead of a duplicate being
+    /// created.
+    pub fn create_item(
+        &self,
+        label: &str,
+        attributes: HashMap<&str, &str>,
+        secret: &[u8],
+        replace: bool,
+        content_type: &str,
+    ) -> Result<Item<'a>, Error> {
+        let (session_path, parameters, value) = self.service.session.encrypt(secret)?;
+        let secret_struct = (session_path, parameters, value, content_type.to_string());
+
+        let (item_path, p_path) = self.proxy().create_item(label, attributes, secret_struct, replace)?;
+        let created = if item_path == Path::new("/")? {
+            self.service.prompt_for_create(&p_path)?
+        } else {
+            item_path
+        };
+        Ok(Item::new(self.service, created))
+    }
+
+    /// Deletes this collection (the D-Bus object, not this struct).
+    pub fn delete(&self) -> Result<(), Error> {
+        let p_path = self.proxy().delete()?;
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.service.prompt_for_lock_unlock_delete(&p_path)
+        }
+    }
+
+    /// Unlocks this collection.
+    pub fn unlock(&self) -> Result<(), Error> {
+        self.service
+            .lock_unlock_all(LockAction::Unlock, vec![self.path.clone()])
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+
+/// The error type returned by fallible calls throughout this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying D-Bus call failed, or the bus returned a D-Bus error
+    /// reply (e.g. `org.freedesktop.DBus.Error.NoSuchObject`).
+    Dbus(dbus::Error),
+    /// A string received from the bus wasn't a valid object path.
+    Parse(String),
+    /// Encrypting or decrypting a secret with the negotiated session key
+    /// failed, e.g. because of a malformed ciphertext.
+    Crypto(&'static str),
+    /// The collection/item/alias that was asked for doesn't exist, or a
+    /// prompt was dismissed by the user, or timed out while waiting for a
+    /// response.
+    NoResult,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Dbus(e) => write!(f, "D-Bus error: {e}"),
+            Error::Parse(s) => write!(f, "invalid D-Bus object path: {s}"),
+            Error::Crypto(msg) => write!(f, "cryptography error: {msg}"),
+            Error::NoResult => write!(f, "no result"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<dbus::Error> for Error {
+    fn from(e: dbus::Error) -> Self {
+        Error::Dbus(e)
+    }
+}
+
+impl From<String> for Error {
+    fn from(s: String) -> Self {
+        Error::Parse(s)
+    }
+}
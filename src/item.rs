@@ -0,0 +1,54 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! A single secret, as returned by [crate::blocking::SecretService::search_items]
+//! or [crate::Collection::create_item].
+
+use dbus::strings::Path;
+
+use crate::proxy::item::Item as ItemProxy;
+use crate::proxy::new_proxy;
+use crate::{blocking::SecretService, Error, LockAction};
+
+/// A secret item, living inside a [crate::Collection].
+pub struct Item<'a> {
+    service: &'a SecretService,
+    pub path: Path<'static>,
+}
+
+impl<'a> Item<'a> {
+    pub(crate) fn new(service: &'a SecretService, path: Path<'static>) -> Self {
+        Item { service, path }
+    }
+
+    fn proxy(&self) -> dbus::blocking::Proxy<'_, &dbus::blocking::Connection> {
+        new_proxy(&self.service.connection, self.path.clone())
+    }
+
+    /// Retrieves and decrypts this item's secret.
+    pub fn get_secret(&self) -> Result<Vec<u8>, Error> {
+        let (_session, parameters, value, _content_type) =
+            self.proxy().get_secret(self.service.session.object_path())?;
+        self.service.session.decrypt(&value, &parameters)
+    }
+
+    /// Deletes this item (the D-Bus object, not this struct).
+    pub fn delete(&self) -> Result<(), Error> {
+        let p_path = self.proxy().delete()?;
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.service.prompt_for_lock_unlock_delete(&p_path)
+        }
+    }
+
+    /// Unlocks this item.
+    pub fn unlock(&self) -> Result<(), Error> {
+        self.service
+            .lock_unlock_all(LockAction::Unlock, vec![self.path.clone()])
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! An async mirror of [crate::blocking], available behind the `async-tokio`
+//! feature.
+//!
+//! Rather than parking a worker thread per call, every D-Bus round trip
+//! (including waiting on an unlock/create prompt) is `.await`ed on top of
+//! [dbus_tokio]'s non-blocking connection. The [Session](crate::session::Session)
+//! and crypto routines, and the overall call shape, mirror [crate::blocking]
+//! exactly so switching between the two is mostly a matter of adding `.await`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::message::MatchRule;
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::strings::Path;
+use dbus_tokio::connection;
+
+use crate::error::Error;
+use crate::session::Session;
+use crate::ss::{SS_COLLECTION_LABEL, SS_DBUS_PATH, SS_ITEM_ATTRIBUTES, SS_ITEM_LABEL};
+use crate::{EncryptionType, LockAction, SearchItemsResult};
+
+const SERVICE_DEST: &str = "org.freedesktop.secrets";
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Async mirror of [crate::blocking::SecretService].
+pub struct SecretService {
+    connection: Arc<SyncConnection>,
+    session: Session,
+    timeout: Option<u64>,
+}
+
+/// Async mirror of [crate::Collection].
+pub struct Collection<'a> {
+    service: &'a SecretService,
+    pub path: Path<'static>,
+}
+
+/// Async mirror of [crate::Item].
+pub struct Item<'a> {
+    service: &'a SecretService,
+    pub path: Path<'static>,
+}
+
+impl SecretService {
+    /// Connects to the session bus and negotiates an encryption session.
+    ///
+    /// Spawns a background task that drives the underlying connection;
+    /// that task runs for the lifetime of the returned [SecretService].
+    pub async fn connect(encryption: EncryptionType) -> Result<Self, Error> {
+        let (resource, connection) = connection::new_session_sync()?;
+        tokio::spawn(async move {
+            let err = resource.await;
+            panic!("lost connection to D-Bus: {err}");
+        });
+
+        let proxy = Proxy::new(SERVICE_DEST, SS_DBUS_PATH, DEFAULT_TIMEOUT, connection.clone());
+        let session = Session::new_async(proxy, encryption).await?;
+
+        Ok(SecretService {
+            connection,
+            session,
+            timeout: None,
+        })
+    }
+
+    /// See [crate::blocking::SecretService::connect_with_max_prompt_timeout].
+    pub async fn connect_with_max_prompt_timeout(
+        encryption: EncryptionType,
+        seconds: u64,
+    ) -> Result<Self, Error> {
+        let mut service = Self::connect(encryption).await?;
+        service.timeout = Some(seconds);
+        Ok(service)
+    }
+
+    fn proxy(&self) -> Proxy<'_, Arc<SyncConnection>> {
+        Proxy::new(SERVICE_DEST, SS_DBUS_PATH, DEFAULT_TIMEOUT, self.connection.clone())
+    }
+
+    /// See [crate::blocking::SecretService::get_all_collections].
+    pub async fn get_all_collections(&self) -> Result<Vec<Collection<'_>>, Error> {
+        let (paths,): (Vec<Path>,) = self
+            .proxy()
+            .method_call(SERVICE_INTERFACE, "Collections", ())
+            .await?;
+        Ok(paths
+            .into_iter()
+            .map(|path| Collection::new(self, path))
+            .collect())
+    }
+
+    /// See [crate::blocking::SecretService::get_collection_by_alias].
+    pub async fn get_collection_by_alias(&self, alias: &str) -> Result<Collection<'_>, Error> {
+        let (path,): (Path,) = self
+            .proxy()
+            .method_call(SERVICE_INTERFACE, "ReadAlias", (alias,))
+            .await?;
+        if path == Path::new("/")? {
+            Err(Error::NoResult)
+        } else {
+            Ok(Collection::new(self, path))
+        }
+    }
+
+    /// See [crate::blocking::SecretService::get_default_collection].
+    pub async fn get_default_collection(&self) -> Result<Collection<'_>, Error> {
+        self.get_collection_by_alias("default").await
+    }
+
+    /// See [crate::blocking::SecretService::get_any_collection].
+    pub async fn get_any_collection(&self) -> Result<Collection<'_>, Error> {
+        if let Ok(collection) = self.get_default_collection().await {
+            return Ok(collection);
+        }
+        if let Ok(collection) = self.get_collection_by_alias("session").await {
+            return Ok(collection);
+        }
+        let mut collections = self.get_all_collections().await?;
+        if collections.is_empty() {
+            Err(Error::NoResult)
+        } else {
+            Ok(collections.swap_remove(0))
+        }
+    }
+
+    /// See [crate::blocking::SecretService::create_collection].
+    pub async fn create_collection(
+        &self,
+        label: &str,
+        alias: &str,
+    ) -> Result<Collection<'_>, Error> {
+        let mut properties: PropMap = HashMap::new();
+        properties.insert(
+            SS_COLLECTION_LABEL.to_string(),
+            Variant(Box::new(label.to_string()) as Box<dyn RefArg>),
+        );
+        let (c_path, p_path): (Path, Path) = self
+            .proxy()
+            .method_call(SERVICE_INTERFACE, "CreateCollection", (properties, alias))
+            .await?;
+        let created = if c_path == Path::new("/")? {
+            self.prompt(&p_path).await?
+        } else {
+            c_path
+        };
+        Ok(Collection::new(self, created))
+    }
+
+    /// See [crate::blocking::SecretService::search_items].
+    pub async fn search_items(
+        &self,
+        attributes: HashMap<&str, &str>,
+    ) -> Result<SearchItemsResult<Item<'_>>, Error> {
+        let (unlocked, locked): (Vec<Path>, Vec<Path>) = self
+            .proxy()
+            .method_call(SERVICE_INTERFACE, "SearchItems", (attributes,))
+            .await?;
+        Ok(SearchItemsResult {
+            unlocked: unlocked.into_iter().map(|p| Item::new(self, p)).collect(),
+            locked: locked.into_iter().map(|p| Item::new(self, p)).collect(),
+        })
+    }
+
+    /// See [crate::blocking::SecretService::lock_all].
+    pub async fn lock_all(&self, items: &[&Item<'_>]) -> Result<(), Error> {
+        let paths = items.iter().map(|i| i.path.clone()).collect();
+        self.lock_unlock_all(LockAction::Lock, paths).await
+    }
+
+    /// See [crate::blocking::SecretService::unlock_all].
+    pub async fn unlock_all(&self, items: &[&Item<'_>]) -> Result<(), Error> {
+        let paths = items.iter().map(|i| i.path.clone()).collect();
+        self.lock_unlock_all(LockAction::Unlock, paths).await
+    }
+
+    pub(crate) async fn lock_unlock_all(
+        &self,
+        action: LockAction,
+        paths: Vec<Path<'static>>,
+    ) -> Result<(), Error> {
+        let method = match action {
+            LockAction::Lock => "Lock",
+            LockAction::Unlock => "Unlock",
+        };
+        let (_, p_path): (Vec<Path>, Path) = self
+            .proxy()
+            .method_call(SERVICE_INTERFACE, method, (paths,))
+            .await?;
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.prompt(&p_path).await.map(|_| ())
+        }
+    }
+
+    /// Drives the `org.freedesktop.Secret.Prompt` dance for a prompt path
+    /// returned by a service call: calls `Prompt`, then waits for the
+    /// `Completed` signal (not a method reply) that fires once the user has
+    /// responded, bounded by the configured
+    /// [timeout](Self::connect_with_max_prompt_timeout) if any.
+    ///
+    /// As documented on [crate::blocking::SecretService::connect_with_max_prompt_timeout],
+    /// a configured timeout of 0 seconds prevents the prompt from appearing
+    /// at all, so that case returns [Error::NoResult] without ever calling
+    /// `Prompt`.
+    async fn prompt(&self, path: &Path<'static>) -> Result<Path<'static>, Error> {
+        if self.timeout == Some(0) {
+            return Err(Error::NoResult);
+        }
+
+        let mut rule = MatchRule::new_signal(PROMPT_INTERFACE, "Completed");
+        rule.path = Some(path.clone());
+        // Restrict to the real secret service, so another process on the
+        // same session bus can't spoof `Completed` and force a prompt
+        // through without the user ever seeing it.
+        rule.sender = Some(SERVICE_DEST.into());
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        let msg_match = self
+            .connection
+            .add_match(rule)
+            .await?
+            .cb(move |_, signal: PromptCompleted| {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(signal);
+                }
+                false
+            });
+        let token = msg_match.token();
+
+        let proxy = Proxy::new(SERVICE_DEST, path.clone(), DEFAULT_TIMEOUT, self.connection.clone());
+        let outcome = match proxy.method_call(PROMPT_INTERFACE, "Prompt", ("",)).await {
+            Err(e) => Err(e.into()),
+            Ok(()) => match self.timeout {
+                Some(seconds) => tokio::time::timeout(Duration::from_secs(seconds), rx)
+                    .await
+                    .map_err(|_| Error::NoResult)
+                    .and_then(|r| r.map_err(|_| Error::NoResult)),
+                None => rx.await.map_err(|_| Error::NoResult),
+            },
+        };
+
+        // Always tear down the match, regardless of how `outcome` turned
+        // out, so a dismissed/timed-out prompt doesn't leak a registration
+        // (and its closure) on the connection for the life of `self`.
+        let _ = self.connection.remove_match(token).await;
+
+        let signal = outcome?;
+        if signal.dismissed {
+            Err(Error::NoResult)
+        } else {
+            dbus::arg::cast::<Path<'static>>(&*signal.result.0)
+                .cloned()
+                .ok_or(Error::NoResult)
+        }
+    }
+}
+
+/// The `Completed(Boolean dismissed, Variant result)` signal emitted by
+/// `org.freedesktop.Secret.Prompt`.
+#[derive(Debug)]
+struct PromptCompleted {
+    dismissed: bool,
+    result: Variant<Box<dyn RefArg>>,
+}
+
+impl dbus::arg::ReadAll for PromptCompleted {
+    fn read(i: &mut dbus::arg::Iter) -> Result<Self, dbus::arg::TypeMismatchError> {
+        Ok(PromptCompleted {
+            dismissed: i.read()?,
+            result: i.read()?,
+        })
+    }
+}
+
+impl dbus::message::SignalArgs for PromptCompleted {
+    const NAME: &'static str = "Completed";
+    const INTERFACE: &'static str = PROMPT_INTERFACE;
+}
+
+impl<'a> Collection<'a> {
+    pub(crate) fn new(service: &'a SecretService, path: Path<'static>) -> Self {
+        Collection { service, path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, Arc<SyncConnection>> {
+        Proxy::new(
+            SERVICE_DEST,
+            self.path.clone(),
+            DEFAULT_TIMEOUT,
+            self.service.connection.clone(),
+        )
+    }
+
+    /// See `Collection::create_item` in [crate::blocking].
+    pub async fn create_item(
+        &self,
+        label: &str,
+        attributes: HashMap<&str, &str>,
+        secret: &[u8],
+        replace: bool,
+        content_type: &str,
+    ) -> Result<Item<'a>, Error> {
+        let (session_path, parameters, value) = self.service.session.encrypt(secret)?;
+        let secret_struct = (session_path, parameters, value, content_type.to_string());
+
+        let mut properties: PropMap = HashMap::new();
+        properties.insert(
+            SS_ITEM_LABEL.to_string(),
+            Variant(Box::new(label.to_string()) as Box<dyn RefArg>),
+        );
+        properties.insert(
+            SS_ITEM_ATTRIBUTES.to_string(),
+            Variant(Box::new(attributes.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>()) as Box<dyn RefArg>),
+        );
+
+        let (item_path, p_path): (Path, Path) = self
+            .proxy()
+            .method_call(
+                COLLECTION_INTERFACE,
+                "CreateItem",
+                (properties, secret_struct, replace),
+            )
+            .await?;
+        let created = if item_path == Path::new("/")? {
+            self.service.prompt(&p_path).await?
+        } else {
+            item_path
+        };
+        Ok(Item::new(self.service, created))
+    }
+
+    /// See `Collection::delete` in [crate::blocking].
+    pub async fn delete(&self) -> Result<(), Error> {
+        let (p_path,): (Path,) = self
+            .proxy()
+            .method_call(COLLECTION_INTERFACE, "Delete", ())
+            .await?;
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.service.prompt(&p_path).await.map(|_| ())
+        }
+    }
+
+    /// See `Collection::unlock` in [crate::blocking].
+    pub async fn unlock(&self) -> Result<(), Error> {
+        self.service
+            .lock_unlock_all(LockAction::Unlock, vec![self.path.clone()])
+            .await
+    }
+}
+
+impl<'a> Item<'a> {
+    pub(crate) fn new(service: &'a SecretService, path: Path<'static>) -> Self {
+        Item { service, path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, Arc<SyncConnection>> {
+        Proxy::new(
+            SERVICE_DEST,
+            self.path.clone(),
+            DEFAULT_TIMEOUT,
+            self.service.connection.clone(),
+        )
+    }
+
+    /// See `Item::get_secret` in [crate::blocking].
+    pub async fn get_secret(&self) -> Result<Vec<u8>, Error> {
+        let (_session, parameters, value, _content_type): (Path, Vec<u8>, Vec<u8>, String) = self
+            .proxy()
+            .method_call(
+                ITEM_INTERFACE,
+                "GetSecret",
+                (self.service.session.object_path(),),
+            )
+            .await?;
+        self.service.session.decrypt(&value, &parameters)
+    }
+
+    /// See `Item::delete` in [crate::blocking].
+    pub async fn delete(&self) -> Result<(), Error> {
+        let (p_path,): (Path,) = self.proxy().method_call(ITEM_INTERFACE, "Delete", ()).await?;
+        if p_path == Path::new("/")? {
+            Ok(())
+        } else {
+            self.service.prompt(&p_path).await.map(|_| ())
+        }
+    }
+
+    /// See `Item::unlock` in [crate::blocking].
+    pub async fn unlock(&self) -> Result<(), Error> {
+        self.service
+            .lock_unlock_all(LockAction::Unlock, vec![self.path.clone()])
+            .await
+    }
+}
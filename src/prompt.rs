@@ -0,0 +1,131 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Drives the `org.freedesktop.Secret.Prompt` dance blocking calls on
+//! [crate::blocking::SecretService] fall into whenever the service can't
+//! satisfy a request (creating a collection/item, or locking/unlocking one)
+//! without asking the user first.
+//!
+//! See [non_blocking's mirror](crate::non_blocking) of this same dance for
+//! the async version.
+
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use dbus::arg::{RefArg, Variant};
+use dbus::message::{MatchRule, Message};
+use dbus::strings::Path;
+
+use crate::blocking::SecretService;
+use crate::proxy::new_proxy;
+use crate::ss::{SS_DBUS_DEST, SS_INTERFACE_PROMPT};
+use crate::Error;
+
+struct PromptCompleted {
+    dismissed: bool,
+    result: Variant<Box<dyn RefArg>>,
+}
+
+impl SecretService {
+    /// Drives a prompt that, once completed, hands back the object path of
+    /// whatever was created (a collection or item).
+    pub(crate) fn prompt_for_create(&self, path: &Path<'static>) -> Result<Path<'static>, Error> {
+        let signal = self.run_prompt(path)?;
+        if signal.dismissed {
+            Err(Error::NoResult)
+        } else {
+            dbus::arg::cast::<Path<'static>>(&*signal.result.0)
+                .cloned()
+                .ok_or(Error::NoResult)
+        }
+    }
+
+    /// Drives a prompt whose `Completed` result is unused (an empty array),
+    /// where only whether the user dismissed it matters.
+    pub(crate) fn prompt_for_lock_unlock_delete(&self, path: &Path<'static>) -> Result<(), Error> {
+        let signal = self.run_prompt(path)?;
+        if signal.dismissed {
+            Err(Error::NoResult)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Calls `Prompt` on the object at `path`, then pumps the connection
+    /// until its `Completed` signal arrives, bounded by the configured
+    /// [timeout](Self::connect_with_max_prompt_timeout) if any.
+    ///
+    /// Specifying 0 for that timeout prevents the prompt from appearing at
+    /// all: this returns [Error::NoResult] before ever calling `Prompt`.
+    fn run_prompt(&self, path: &Path<'static>) -> Result<PromptCompleted, Error> {
+        if self.timeout == Some(0) {
+            return Err(Error::NoResult);
+        }
+
+        let mut rule = MatchRule::new();
+        rule.interface = Some(SS_INTERFACE_PROMPT.into());
+        rule.path = Some(path.clone());
+        // Restrict to the real secret service, so another process on the
+        // same session bus can't spoof `Completed` and force a prompt
+        // through without the user ever seeing it.
+        rule.sender = Some(SS_DBUS_DEST.into());
+
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(Some(tx));
+        let token = self.connection.add_match(rule, move |_: (), _, msg| {
+            if let Some(signal) = decode_completed(msg) {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(signal);
+                }
+            }
+            true
+        })?;
+
+        let outcome = new_proxy(&self.connection, path.clone())
+            .method_call(SS_INTERFACE_PROMPT, "Prompt", ("",))
+            .map_err(Error::from)
+            .and_then(|()| {
+                let deadline = self
+                    .timeout
+                    .map(|seconds| Instant::now() + Duration::from_secs(seconds));
+                loop {
+                    if let Ok(signal) = rx.try_recv() {
+                        break Ok(signal);
+                    }
+                    if deadline.is_some_and(|d| Instant::now() >= d) {
+                        break Err(Error::NoResult);
+                    }
+                    self.connection.process(Duration::from_millis(100))?;
+                }
+            });
+
+        // Always tear down the match, regardless of how `outcome` turned
+        // out, so a dismissed/timed-out prompt doesn't leak a registration
+        // (and its closure) on the connection for the life of `self`.
+        let _ = self.connection.remove_match(token);
+
+        // A timeout means the `Completed` signal never arrived, so the
+        // prompt object is still outstanding on the service side. Dismiss it
+        // so it doesn't linger (and so a retried operation doesn't race a
+        // still-live prompt from this attempt).
+        if matches!(outcome, Err(Error::NoResult)) {
+            let _ = new_proxy(&self.connection, path.clone()).method_call::<(), _, _, _>(
+                SS_INTERFACE_PROMPT,
+                "Dismiss",
+                (),
+            );
+        }
+
+        outcome
+    }
+}
+
+fn decode_completed(msg: &Message) -> Option<PromptCompleted> {
+    let (dismissed, result) = msg.read2().ok()?;
+    Some(PromptCompleted { dismissed, result })
+}
@@ -0,0 +1,60 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Bindings for the `org.freedesktop.Secret.Collection` interface.
+
+use std::collections::HashMap;
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::{BlockingSender, Proxy};
+use dbus::strings::Path;
+use dbus::Error;
+
+use crate::ss::{SS_INTERFACE_COLLECTION, SS_ITEM_ATTRIBUTES, SS_ITEM_LABEL};
+
+/// Methods on `org.freedesktop.Secret.Collection`.
+pub(crate) trait Collection {
+    fn create_item(
+        &self,
+        label: &str,
+        attributes: HashMap<&str, &str>,
+        secret: (Path<'static>, Vec<u8>, Vec<u8>, String),
+        replace: bool,
+    ) -> Result<(Path<'static>, Path<'static>), Error>;
+    fn delete(&self) -> Result<Path<'static>, Error>;
+}
+
+impl<'a, T: BlockingSender, C: std::ops::Deref<Target = T>> Collection for Proxy<'a, C> {
+    fn create_item(
+        &self,
+        label: &str,
+        attributes: HashMap<&str, &str>,
+        secret: (Path<'static>, Vec<u8>, Vec<u8>, String),
+        replace: bool,
+    ) -> Result<(Path<'static>, Path<'static>), Error> {
+        let mut properties: PropMap = HashMap::new();
+        properties.insert(
+            SS_ITEM_LABEL.to_string(),
+            Variant(Box::new(label.to_string()) as Box<dyn RefArg>),
+        );
+        properties.insert(
+            SS_ITEM_ATTRIBUTES.to_string(),
+            Variant(Box::new(
+                attributes
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            ) as Box<dyn RefArg>),
+        );
+        self.method_call(SS_INTERFACE_COLLECTION, "CreateItem", (properties, secret, replace))
+    }
+
+    fn delete(&self) -> Result<Path<'static>, Error> {
+        self.method_call(SS_INTERFACE_COLLECTION, "Delete", ())
+            .map(|(r,)| r)
+    }
+}
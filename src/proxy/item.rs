@@ -0,0 +1,37 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Bindings for the `org.freedesktop.Secret.Item` interface.
+
+use dbus::blocking::{BlockingSender, Proxy};
+use dbus::strings::Path;
+use dbus::Error;
+
+use crate::ss::SS_INTERFACE_ITEM;
+
+/// Methods on `org.freedesktop.Secret.Item`.
+pub(crate) trait Item {
+    fn get_secret(
+        &self,
+        session: Path<'static>,
+    ) -> Result<(Path<'static>, Vec<u8>, Vec<u8>, String), Error>;
+    fn delete(&self) -> Result<Path<'static>, Error>;
+}
+
+impl<'a, T: BlockingSender, C: std::ops::Deref<Target = T>> Item for Proxy<'a, C> {
+    fn get_secret(
+        &self,
+        session: Path<'static>,
+    ) -> Result<(Path<'static>, Vec<u8>, Vec<u8>, String), Error> {
+        self.method_call(SS_INTERFACE_ITEM, "GetSecret", (session,))
+            .map(|(r,)| r)
+    }
+
+    fn delete(&self) -> Result<Path<'static>, Error> {
+        self.method_call(SS_INTERFACE_ITEM, "Delete", ()).map(|(r,)| r)
+    }
+}
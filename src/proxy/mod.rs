@@ -0,0 +1,33 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Thin, hand-written extension traits over [dbus::blocking::Proxy], one per
+//! `org.freedesktop.Secret.*` interface, in the same shape `dbus-codegen`
+//! output takes. Used only by [crate::blocking]; [crate::non_blocking] talks
+//! to the bus directly through [dbus::nonblock::Proxy::method_call] instead.
+
+use std::time::Duration;
+
+use dbus::blocking::{Connection, Proxy};
+use dbus::strings::Path;
+
+use crate::ss::SS_DBUS_DEST;
+
+pub(crate) mod collection;
+pub(crate) mod item;
+pub(crate) mod service;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builds a [Proxy] to the `org.freedesktop.secrets` service at `path`,
+/// using this crate's default method-call timeout.
+pub(crate) fn new_proxy<'a>(
+    connection: &'a Connection,
+    path: impl Into<Path<'a>>,
+) -> Proxy<'a, &'a Connection> {
+    Proxy::new(SS_DBUS_DEST, path, TIMEOUT, connection)
+}
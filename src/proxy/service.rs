@@ -0,0 +1,90 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Bindings for the `org.freedesktop.Secret.Service` interface.
+
+use std::collections::HashMap;
+
+use dbus::arg::PropMap;
+use dbus::blocking::{BlockingSender, Proxy};
+use dbus::strings::Path;
+use dbus::Error;
+
+use crate::ss::SS_INTERFACE_SERVICE;
+
+/// Methods on `org.freedesktop.Secret.Service`.
+pub(crate) trait Service {
+    fn collections(&self) -> Result<Vec<Path<'static>>, Error>;
+    fn read_alias(&self, name: &str) -> Result<Path<'static>, Error>;
+    fn create_collection(
+        &self,
+        properties: PropMap,
+        alias: &str,
+    ) -> Result<(Path<'static>, Path<'static>), Error>;
+    fn search_items(
+        &self,
+        attributes: HashMap<&str, &str>,
+    ) -> Result<(Vec<Path<'static>>, Vec<Path<'static>>), Error>;
+    #[allow(clippy::type_complexity)]
+    fn get_secrets(
+        &self,
+        items: Vec<Path<'static>>,
+        session: Path<'static>,
+    ) -> Result<HashMap<Path<'static>, (Path<'static>, Vec<u8>, Vec<u8>, String)>, Error>;
+    fn lock(&self, objects: Vec<Path<'static>>) -> Result<(Vec<Path<'static>>, Path<'static>), Error>;
+    fn unlock(
+        &self,
+        objects: Vec<Path<'static>>,
+    ) -> Result<(Vec<Path<'static>>, Path<'static>), Error>;
+}
+
+impl<'a, T: BlockingSender, C: std::ops::Deref<Target = T>> Service for Proxy<'a, C> {
+    fn collections(&self) -> Result<Vec<Path<'static>>, Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "Collections", ())
+            .map(|(r,)| r)
+    }
+
+    fn read_alias(&self, name: &str) -> Result<Path<'static>, Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "ReadAlias", (name,))
+            .map(|(r,)| r)
+    }
+
+    fn create_collection(
+        &self,
+        properties: PropMap,
+        alias: &str,
+    ) -> Result<(Path<'static>, Path<'static>), Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "CreateCollection", (properties, alias))
+    }
+
+    fn search_items(
+        &self,
+        attributes: HashMap<&str, &str>,
+    ) -> Result<(Vec<Path<'static>>, Vec<Path<'static>>), Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "SearchItems", (attributes,))
+    }
+
+    fn get_secrets(
+        &self,
+        items: Vec<Path<'static>>,
+        session: Path<'static>,
+    ) -> Result<HashMap<Path<'static>, (Path<'static>, Vec<u8>, Vec<u8>, String)>, Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "GetSecrets", (items, session))
+            .map(|(r,)| r)
+    }
+
+    fn lock(&self, objects: Vec<Path<'static>>) -> Result<(Vec<Path<'static>>, Path<'static>), Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "Lock", (objects,))
+    }
+
+    fn unlock(
+        &self,
+        objects: Vec<Path<'static>>,
+    ) -> Result<(Vec<Path<'static>>, Path<'static>), Error> {
+        self.method_call(SS_INTERFACE_SERVICE, "Unlock", (objects,))
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The `org.freedesktop.Secret.Service.OpenSession` handshake and the
+//! resulting per-connection [Session], shared by [crate::blocking] and
+//! [crate::non_blocking].
+//!
+//! [EncryptionType::Plain] opens a session with no cryptographic material
+//! at all; [EncryptionType::Dh] performs a Diffie-Hellman key exchange over
+//! the 1024-bit MODP group from RFC 2409 and derives an AES-128 key from
+//! the shared secret with HKDF-SHA256, exactly as the
+//! `dh-ietf1024-sha256-aes128-cbc-pkcs7` algorithm name promises.
+
+use aes::Aes128;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use cbc::{Decryptor, Encryptor};
+use dbus::arg::Variant;
+use dbus::blocking::Proxy as BlockingProxy;
+#[cfg(feature = "async-tokio")]
+use dbus::nonblock::{NonblockReply, Proxy as NonblockProxy};
+use dbus::strings::Path;
+use hkdf::Hkdf;
+use num::BigUint;
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::ss::SS_INTERFACE_SERVICE;
+use crate::Error;
+
+type Aes128CbcEnc = Encryptor<Aes128>;
+type Aes128CbcDec = Decryptor<Aes128>;
+
+// RFC 2409 "Second Oakley Group": a 1024-bit MODP group with generator 2.
+// This is the group the Secret Service spec's `dh-ietf1024-sha256-aes128-cbc-pkcs7`
+// algorithm is named after.
+const PRIME_HEX: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC7",
+    "4020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14",
+    "374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B",
+    "7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFF",
+    "FFFF",
+);
+
+/// The encryption negotiated with the service for a [Session].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionType {
+    /// No encryption: secrets are exchanged in plaintext. Only suitable for
+    /// trusted local connections.
+    Plain,
+    /// AES-128 secrets, with the key derived from a Diffie-Hellman exchange.
+    Dh,
+}
+
+/// A negotiated connection to `org.freedesktop.Secret.Service`, holding
+/// whatever key material (if any) was agreed on at `OpenSession` time.
+pub(crate) struct Session {
+    encryption_type: EncryptionType,
+    object_path: Path<'static>,
+    aes_key: Option<[u8; 16]>,
+}
+
+impl Session {
+    /// Performs the `OpenSession` handshake over a blocking connection.
+    pub(crate) fn new(
+        proxy: BlockingProxy<'_, &dbus::blocking::Connection>,
+        encryption: EncryptionType,
+    ) -> Result<Self, Error> {
+        match encryption {
+            EncryptionType::Plain => {
+                let (_output, path): (Variant<String>, Path<'static>) =
+                    proxy.method_call(SS_INTERFACE_SERVICE, "OpenSession", ("plain", Variant(String::new())))?;
+                Ok(Session {
+                    encryption_type: EncryptionType::Plain,
+                    object_path: path,
+                    aes_key: None,
+                })
+            }
+            EncryptionType::Dh => {
+                let private_key = random_private_key();
+                let public_key = dh_public_key(&private_key);
+                let (output, path): (Variant<Vec<u8>>, Path<'static>) = proxy.method_call(
+                    SS_INTERFACE_SERVICE,
+                    "OpenSession",
+                    (algorithm(EncryptionType::Dh), Variant(public_key.to_bytes_be())),
+                )?;
+                let aes_key = derive_aes_key(&private_key, &output.0)?;
+                Ok(Session {
+                    encryption_type: EncryptionType::Dh,
+                    object_path: path,
+                    aes_key: Some(aes_key),
+                })
+            }
+        }
+    }
+
+    /// Performs the `OpenSession` handshake over a non-blocking connection.
+    /// See [Session::new] for the blocking counterpart.
+    #[cfg(feature = "async-tokio")]
+    pub(crate) async fn new_async<T, C>(
+        proxy: NonblockProxy<'_, C>,
+        encryption: EncryptionType,
+    ) -> Result<Self, Error>
+    where
+        T: NonblockReply,
+        C: std::ops::Deref<Target = T>,
+    {
+        match encryption {
+            EncryptionType::Plain => {
+                let (_output, path): (Variant<String>, Path<'static>) = proxy
+                    .method_call(SS_INTERFACE_SERVICE, "OpenSession", ("plain", Variant(String::new())))
+                    .await?;
+                Ok(Session {
+                    encryption_type: EncryptionType::Plain,
+                    object_path: path,
+                    aes_key: None,
+                })
+            }
+            EncryptionType::Dh => {
+                let private_key = random_private_key();
+                let public_key = dh_public_key(&private_key);
+                let (output, path): (Variant<Vec<u8>>, Path<'static>) = proxy
+                    .method_call(
+                        SS_INTERFACE_SERVICE,
+                        "OpenSession",
+                        (algorithm(EncryptionType::Dh), Variant(public_key.to_bytes_be())),
+                    )
+                    .await?;
+                let aes_key = derive_aes_key(&private_key, &output.0)?;
+                Ok(Session {
+                    encryption_type: EncryptionType::Dh,
+                    object_path: path,
+                    aes_key: Some(aes_key),
+                })
+            }
+        }
+    }
+
+    /// The object path of this session, used as the `session` argument to
+    /// `CreateItem`/`GetSecret`/`GetSecrets`.
+    pub(crate) fn object_path(&self) -> Path<'static> {
+        self.object_path.clone()
+    }
+
+    /// The [EncryptionType] this session was opened with.
+    pub(crate) fn encryption_type(&self) -> EncryptionType {
+        self.encryption_type
+    }
+
+    /// The algorithm name negotiated at `OpenSession` time, as it appears on
+    /// the wire (e.g. `"plain"` or `"dh-ietf1024-sha256-aes128-cbc-pkcs7"`).
+    pub(crate) fn algorithm(&self) -> &'static str {
+        algorithm(self.encryption_type)
+    }
+
+    /// Encrypts `secret` for transport, returning `(session, parameters,
+    /// value)` ready to be sent as the first three fields of a `Secret`
+    /// struct. For [EncryptionType::Dh] sessions `parameters` is the
+    /// randomly generated CBC IV, per the `dh-ietf1024-sha256-aes128-cbc-pkcs7`
+    /// algorithm; for [EncryptionType::Plain] it's empty.
+    pub(crate) fn encrypt(&self, secret: &[u8]) -> Result<(Path<'static>, Vec<u8>, Vec<u8>), Error> {
+        let (parameters, value) = match self.aes_key {
+            None => (Vec::new(), secret.to_vec()),
+            Some(key) => {
+                let mut iv = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut iv);
+                let ciphertext = Aes128CbcEnc::new_from_slices(&key, &iv)
+                    .map_err(|_| Error::Crypto("invalid AES key/IV length"))?
+                    .encrypt_padded_vec_mut::<Pkcs7>(secret);
+                (iv.to_vec(), ciphertext)
+            }
+        };
+        Ok((self.object_path(), parameters, value))
+    }
+
+    /// Decrypts a secret `value` previously returned by `GetSecret`(s),
+    /// using `parameters` as the CBC IV.
+    pub(crate) fn decrypt(&self, value: &[u8], parameters: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(key) = self.aes_key else {
+            return Ok(value.to_vec());
+        };
+        Aes128CbcDec::new_from_slices(&key, parameters)
+            .map_err(|_| Error::Crypto("invalid AES key/IV length"))?
+            .decrypt_padded_vec_mut::<Pkcs7>(value)
+            .map_err(|_| Error::Crypto("failed to decrypt secret"))
+    }
+}
+
+fn algorithm(encryption: EncryptionType) -> &'static str {
+    match encryption {
+        EncryptionType::Plain => "plain",
+        EncryptionType::Dh => "dh-ietf1024-sha256-aes128-cbc-pkcs7",
+    }
+}
+
+fn prime() -> BigUint {
+    BigUint::parse_bytes(PRIME_HEX.as_bytes(), 16).expect("PRIME_HEX is a valid hex literal")
+}
+
+fn random_private_key() -> BigUint {
+    let mut bytes = [0u8; 128]; // 1024 bits, matching the MODP group's modulus size
+    rand::thread_rng().fill_bytes(&mut bytes);
+    BigUint::from_bytes_be(&bytes) % prime()
+}
+
+fn dh_public_key(private_key: &BigUint) -> BigUint {
+    BigUint::from(2u8).modpow(private_key, &prime())
+}
+
+fn derive_aes_key(private_key: &BigUint, their_public_key: &[u8]) -> Result<[u8; 16], Error> {
+    let their_public_key = BigUint::from_bytes_be(their_public_key);
+    let shared_secret = their_public_key.modpow(private_key, &prime());
+    let ikm = shared_secret.to_bytes_be();
+    let hk = Hkdf::<Sha256>::new(None, &ikm);
+    let mut okm = [0u8; 16];
+    hk.expand(&[], &mut okm)
+        .map_err(|_| Error::Crypto("HKDF output length is larger than SHA-256 allows"))?;
+    Ok(okm)
+}
@@ -0,0 +1,22 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Well-known names from the
+//! [Secret Service spec](https://standards.freedesktop.org/secret-service/)
+//! shared across [crate::blocking] and [crate::non_blocking].
+
+pub(crate) const SS_DBUS_DEST: &str = "org.freedesktop.secrets";
+pub(crate) const SS_DBUS_PATH: &str = "/org/freedesktop/secrets";
+
+pub(crate) const SS_INTERFACE_SERVICE: &str = "org.freedesktop.Secret.Service";
+pub(crate) const SS_INTERFACE_COLLECTION: &str = "org.freedesktop.Secret.Collection";
+pub(crate) const SS_INTERFACE_ITEM: &str = "org.freedesktop.Secret.Item";
+pub(crate) const SS_INTERFACE_PROMPT: &str = "org.freedesktop.Secret.Prompt";
+
+pub(crate) const SS_COLLECTION_LABEL: &str = "org.freedesktop.Secret.Collection.Label";
+pub(crate) const SS_ITEM_LABEL: &str = "org.freedesktop.Secret.Item.Label";
+pub(crate) const SS_ITEM_ATTRIBUTES: &str = "org.freedesktop.Secret.Item.Attributes";
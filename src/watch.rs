@@ -0,0 +1,155 @@
+// Copyright 2016-2024 dbus-secret-service Contributors
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::time::Duration;
+
+use dbus::blocking::Connection;
+use dbus::channel::Token;
+use dbus::message::{MatchRule, Message};
+use dbus::strings::Path;
+
+use crate::blocking::SecretService;
+use crate::{Collection, Error};
+
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+
+/// An event reported by [SecretService::watch_collection], describing a
+/// change another process made to the watched collection.
+#[derive(Debug, Clone)]
+pub enum CollectionEvent {
+    /// A new item was created at this object path.
+    ItemCreated(Path<'static>),
+    /// The item at this object path was deleted.
+    ItemDeleted(Path<'static>),
+    /// The item at this object path changed.
+    ItemChanged(Path<'static>),
+}
+
+/// An event reported by [SecretService::watch_service], describing a
+/// collection another process created, deleted, or changed.
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    /// A new collection was created at this object path.
+    CollectionCreated(Path<'static>),
+    /// The collection at this object path was deleted.
+    CollectionDeleted(Path<'static>),
+    /// The collection at this object path changed.
+    CollectionChanged(Path<'static>),
+}
+
+/// A guard returned by [SecretService::watch_collection].
+///
+/// Dropping it removes the underlying D-Bus match rule, after which
+/// `callback` is no longer invoked.
+pub struct WatchGuard<'a> {
+    connection: &'a Connection,
+    token: Token,
+}
+
+impl Drop for WatchGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.connection.remove_match(self.token);
+    }
+}
+
+impl SecretService {
+    /// Watches `collection` for `ItemCreated`, `ItemDeleted` and
+    /// `ItemChanged` signals, invoking `callback` with a [CollectionEvent]
+    /// for each one.
+    ///
+    /// Incoming signals are only dispatched while something is pumping the
+    /// connection, so callers must drive [process_events](Self::process_events)
+    /// (or another code path that reads from the same `Connection`) in a
+    /// loop of their own. Dropping the returned guard removes the match
+    /// rule, after which `callback` stops being invoked.
+    pub fn watch_collection(
+        &self,
+        collection: &Collection,
+        mut callback: impl FnMut(CollectionEvent) + Send + 'static,
+    ) -> Result<WatchGuard<'_>, Error> {
+        let mut rule = MatchRule::new();
+        rule.interface = Some(COLLECTION_INTERFACE.into());
+        rule.path = Some(collection.path.clone());
+
+        let token = self
+            .connection
+            .add_match(rule, move |_: (), _, msg| {
+                if let Some(event) = decode_event(msg) {
+                    callback(event);
+                }
+                true
+            })?;
+
+        Ok(WatchGuard {
+            connection: &self.connection,
+            token,
+        })
+    }
+
+    /// Watches the service itself for `CollectionCreated`, `CollectionDeleted`
+    /// and `CollectionChanged` signals, invoking `callback` with a
+    /// [ServiceEvent] for each one.
+    ///
+    /// This is the service-wide counterpart to
+    /// [watch_collection](Self::watch_collection): it's how a long-lived
+    /// caller learns that a collection appeared, disappeared, or was renamed,
+    /// rather than just that an already-known collection's items changed.
+    /// The same pumping and lifetime rules as `watch_collection` apply:
+    /// drive [process_events](Self::process_events) in a loop, and drop the
+    /// returned guard to stop watching.
+    pub fn watch_service(
+        &self,
+        mut callback: impl FnMut(ServiceEvent) + Send + 'static,
+    ) -> Result<WatchGuard<'_>, Error> {
+        let mut rule = MatchRule::new();
+        rule.interface = Some(SERVICE_INTERFACE.into());
+
+        let token = self
+            .connection
+            .add_match(rule, move |_: (), _, msg| {
+                if let Some(event) = decode_service_event(msg) {
+                    callback(event);
+                }
+                true
+            })?;
+
+        Ok(WatchGuard {
+            connection: &self.connection,
+            token,
+        })
+    }
+
+    /// Pumps the underlying D-Bus connection for up to `timeout`,
+    /// dispatching any pending signals to watchers registered via
+    /// [watch_collection](Self::watch_collection) or
+    /// [watch_service](Self::watch_service).
+    pub fn process_events(&self, timeout: Duration) -> Result<(), Error> {
+        self.connection.process(timeout)?;
+        Ok(())
+    }
+}
+
+fn decode_event(msg: &Message) -> Option<CollectionEvent> {
+    let path: Path<'static> = msg.read1().ok()?;
+    match msg.member().as_deref() {
+        Some("ItemCreated") => Some(CollectionEvent::ItemCreated(path)),
+        Some("ItemDeleted") => Some(CollectionEvent::ItemDeleted(path)),
+        Some("ItemChanged") => Some(CollectionEvent::ItemChanged(path)),
+        _ => None,
+    }
+}
+
+fn decode_service_event(msg: &Message) -> Option<ServiceEvent> {
+    let path: Path<'static> = msg.read1().ok()?;
+    match msg.member().as_deref() {
+        Some("CollectionCreated") => Some(ServiceEvent::CollectionCreated(path)),
+        Some("CollectionDeleted") => Some(ServiceEvent::CollectionDeleted(path)),
+        Some("CollectionChanged") => Some(ServiceEvent::CollectionChanged(path)),
+        _ => None,
+    }
+}